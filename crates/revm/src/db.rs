@@ -2,15 +2,21 @@
 
 //#[cfg(feature = "alloydb")]
 //pub mod alloydb;
+#[cfg(feature = "std")]
+pub mod boxed_db;
 pub mod emptydb;
 #[cfg(feature = "ethersdb")]
 pub mod ethersdb;
 pub mod in_memory_db;
 pub mod states;
+#[cfg(feature = "std")]
+pub mod warm_cache;
 
 pub use crate::primitives::db::*;
 //#[cfg(feature = "alloydb")]
 //pub use alloydb::AlloyDB;
+#[cfg(feature = "std")]
+pub use boxed_db::{BoxedDb, BoxedDbError};
 pub use emptydb::{EmptyDB, EmptyDBTyped};
 #[cfg(feature = "ethersdb")]
 pub use ethersdb::EthersDB;
@@ -20,3 +26,5 @@ pub use states::{
     OriginalValuesKnown, PlainAccount, RevertToSlot, State, StateBuilder, StateDBBox,
     StorageWithOriginalValues, TransitionAccount, TransitionState,
 };
+#[cfg(feature = "std")]
+pub use warm_cache::WarmCache;
@@ -0,0 +1,174 @@
+use super::{Database, DatabaseRef};
+use crate::primitives::{AccountInfo, Address, Bytecode, B256, U256};
+use core::fmt;
+use std::{boxed::Box, error::Error};
+
+/// Type-erased error returned by [BoxedDb].
+pub type BoxedDbError = Box<dyn Error + Send + Sync>;
+
+/// A [DatabaseRef] behind a trait object, with its error type erased into a [BoxedDbError].
+///
+/// This allows choosing the backend of a [CacheDB](crate::db::CacheDB) at runtime, as
+/// `CacheDB<BoxedDb>` is not generic over the backend.
+///
+/// The boxed database must be `Send + 'static`, and its error type must implement [Error] and
+/// be `Send + Sync`. Backends holding an `Rc`, or with error types that don't implement [Error],
+/// have to be wrapped before they can be boxed.
+pub struct BoxedDb(Box<dyn DatabaseRef<Error = BoxedDbError> + Send>);
+
+impl BoxedDb {
+    /// Boxes the given database.
+    pub fn new<DB>(db: DB) -> Self
+    where
+        DB: DatabaseRef + Send + 'static,
+        DB::Error: Error + Send + Sync + 'static,
+    {
+        Self(Box::new(ErasedErrorDb(db)))
+    }
+}
+
+impl fmt::Debug for BoxedDb {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BoxedDb").finish_non_exhaustive()
+    }
+}
+
+impl DatabaseRef for BoxedDb {
+    type Error = BoxedDbError;
+
+    #[inline]
+    fn basic_ref(&self, address: Address) -> Result<Option<AccountInfo>, Self::Error> {
+        self.0.basic_ref(address)
+    }
+
+    #[inline]
+    fn code_by_hash_ref(&self, code_hash: B256) -> Result<Bytecode, Self::Error> {
+        self.0.code_by_hash_ref(code_hash)
+    }
+
+    #[inline]
+    fn storage_ref(&self, address: Address, index: U256) -> Result<U256, Self::Error> {
+        self.0.storage_ref(address, index)
+    }
+
+    #[inline]
+    fn block_hash_ref(&self, number: U256) -> Result<B256, Self::Error> {
+        self.0.block_hash_ref(number)
+    }
+}
+
+impl Database for BoxedDb {
+    type Error = BoxedDbError;
+
+    #[inline]
+    fn basic(&mut self, address: Address) -> Result<Option<AccountInfo>, Self::Error> {
+        self.basic_ref(address)
+    }
+
+    #[inline]
+    fn code_by_hash(&mut self, code_hash: B256) -> Result<Bytecode, Self::Error> {
+        self.code_by_hash_ref(code_hash)
+    }
+
+    #[inline]
+    fn storage(&mut self, address: Address, index: U256) -> Result<U256, Self::Error> {
+        self.storage_ref(address, index)
+    }
+
+    #[inline]
+    fn block_hash(&mut self, number: U256) -> Result<B256, Self::Error> {
+        self.block_hash_ref(number)
+    }
+}
+
+/// Converts the errors of the wrapped database into [BoxedDbError].
+struct ErasedErrorDb<DB>(DB);
+
+impl<DB> DatabaseRef for ErasedErrorDb<DB>
+where
+    DB: DatabaseRef,
+    DB::Error: Error + Send + Sync + 'static,
+{
+    type Error = BoxedDbError;
+
+    #[inline]
+    fn basic_ref(&self, address: Address) -> Result<Option<AccountInfo>, Self::Error> {
+        self.0.basic_ref(address).map_err(Into::into)
+    }
+
+    #[inline]
+    fn code_by_hash_ref(&self, code_hash: B256) -> Result<Bytecode, Self::Error> {
+        self.0.code_by_hash_ref(code_hash).map_err(Into::into)
+    }
+
+    #[inline]
+    fn storage_ref(&self, address: Address, index: U256) -> Result<U256, Self::Error> {
+        self.0.storage_ref(address, index).map_err(Into::into)
+    }
+
+    #[inline]
+    fn block_hash_ref(&self, number: U256) -> Result<B256, Self::Error> {
+        self.0.block_hash_ref(number).map_err(Into::into)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::{CacheDB, EmptyDB};
+
+    #[derive(Debug)]
+    struct NotFound;
+
+    impl fmt::Display for NotFound {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str("not found")
+        }
+    }
+
+    impl Error for NotFound {}
+
+    /// Database that knows a single account and fails on everything else.
+    struct SingleAccountDb;
+
+    impl DatabaseRef for SingleAccountDb {
+        type Error = NotFound;
+
+        fn basic_ref(&self, address: Address) -> Result<Option<AccountInfo>, Self::Error> {
+            if address == Address::with_last_byte(42) {
+                Ok(Some(AccountInfo {
+                    nonce: 42,
+                    ..Default::default()
+                }))
+            } else {
+                Err(NotFound)
+            }
+        }
+
+        fn code_by_hash_ref(&self, _code_hash: B256) -> Result<Bytecode, Self::Error> {
+            Err(NotFound)
+        }
+
+        fn storage_ref(&self, _address: Address, _index: U256) -> Result<U256, Self::Error> {
+            Err(NotFound)
+        }
+
+        fn block_hash_ref(&self, _number: U256) -> Result<B256, Self::Error> {
+            Err(NotFound)
+        }
+    }
+
+    #[test]
+    fn test_swap_backends() {
+        let account = Address::with_last_byte(42);
+        let other = Address::with_last_byte(1);
+
+        let mut state = CacheDB::new(BoxedDb::new(EmptyDB::default()));
+        assert_eq!(state.basic(account).unwrap(), None);
+        assert_eq!(state.basic(other).unwrap(), None);
+
+        let mut state = CacheDB::new(BoxedDb::new(SingleAccountDb));
+        assert_eq!(state.basic(account).unwrap().unwrap().nonce, 42);
+        assert_eq!(state.basic(other).unwrap_err().to_string(), "not found");
+    }
+}
@@ -5,7 +5,7 @@ use crate::primitives::{
 };
 use crate::Database;
 use core::convert::Infallible;
-use std::vec::Vec;
+use std::{collections::BTreeMap, vec::Vec};
 
 /// A [Database] implementation that stores all state changes in memory.
 pub type InMemoryDB = CacheDB<EmptyDB>;
@@ -33,6 +33,16 @@ pub struct CacheDB<ExtDB> {
     ///
     /// Note: this is read-only, data is never written to this database.
     pub db: ExtDB,
+    /// Whether the cache is cleared at every [CacheDB::begin_transaction] call.
+    ///
+    /// This is runtime configuration rather than cached state, so it is not serialized.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    auto_clear: bool,
+    /// Eviction state if the number of cached accounts is bounded.
+    ///
+    /// See [CacheDB::with_account_capacity].
+    #[cfg_attr(feature = "serde", serde(skip))]
+    lru: Option<AccountLru>,
 }
 
 impl<ExtDB: Default> Default for CacheDB<ExtDB> {
@@ -43,18 +53,128 @@ impl<ExtDB: Default> Default for CacheDB<ExtDB> {
 
 impl<ExtDB> CacheDB<ExtDB> {
     pub fn new(db: ExtDB) -> Self {
-        let mut contracts = HashMap::new();
-        contracts.insert(KECCAK_EMPTY, Bytecode::default());
-        contracts.insert(B256::ZERO, Bytecode::default());
+        let mut cache = Self::new_empty(db);
+        cache.contracts.insert(KECCAK_EMPTY, Bytecode::default());
+        cache.contracts.insert(B256::ZERO, Bytecode::default());
+        cache
+    }
+
+    /// Creates a new cache without the default empty-code entries in `contracts`.
+    ///
+    /// [CacheDB::new] pre-populates `contracts` with empty bytecode for [KECCAK_EMPTY] and
+    /// [B256::ZERO]. This constructor skips them so the cache starts out truly empty, which is
+    /// useful for precise accounting. Callers are responsible for handling empty-code lookups
+    /// themselves, as these will now be forwarded to the underlying database.
+    pub fn new_empty(db: ExtDB) -> Self {
         Self {
             accounts: HashMap::new(),
-            contracts,
+            contracts: HashMap::new(),
             logs: Vec::default(),
             block_hashes: HashMap::new(),
             db,
+            auto_clear: false,
+            lru: None,
+        }
+    }
+
+    /// Creates a new cache that holds at most `capacity` accounts.
+    ///
+    /// When a new account has to be loaded and the cache is full, the least recently used
+    /// account that was loaded from the underlying database is evicted. Evicted accounts are
+    /// loaded again from the underlying database on their next access.
+    ///
+    /// Accounts that were modified through [CacheDB::load_account], the `insert_*`/`replace_*`
+    /// methods or [DatabaseCommit::commit] can't be restored from the underlying database, so
+    /// they are never evicted. If there are more of them than `capacity`, the cache grows beyond
+    /// it. Modifications made directly to `accounts` are not tracked.
+    ///
+    /// Note: the eviction state is not serialized, a deserialized cache is unbounded.
+    pub fn with_account_capacity(db: ExtDB, capacity: usize) -> Self {
+        let mut cache = Self::new(db);
+        cache.lru = Some(AccountLru::new(capacity));
+        cache
+    }
+
+    /// Returns the maximum number of accounts set by [CacheDB::with_account_capacity].
+    pub fn account_capacity(&self) -> Option<usize> {
+        self.lru.as_ref().map(|lru| lru.capacity)
+    }
+
+    /// Returns `true` if the cache is cleared at every [CacheDB::begin_transaction] call.
+    pub fn auto_clear(&self) -> bool {
+        self.auto_clear
+    }
+
+    /// Sets whether the cache is cleared at every [CacheDB::begin_transaction] call.
+    ///
+    /// This is meant for benchmarking with a cold cache: every transaction has to load all of its
+    /// state from the underlying database, which is considerably slower than running on a warm
+    /// cache.
+    pub fn set_auto_clear(&mut self, auto_clear: bool) {
+        self.auto_clear = auto_clear;
+    }
+
+    /// Signals the start of a new transaction.
+    ///
+    /// If auto clear is enabled, this calls [CacheDB::clear_cache], otherwise it does nothing.
+    /// It should be called before the transaction is executed, after the previous transaction
+    /// was committed.
+    pub fn begin_transaction(&mut self) {
+        if self.auto_clear {
+            self.clear_cache();
         }
     }
 
+    /// Clears all cached accounts, contracts and block hashes.
+    ///
+    /// The default empty-code entries in `contracts` are kept, logs are not touched.
+    ///
+    /// Note: the underlying database is read-only, so this also discards all state that was
+    /// committed to the cache.
+    pub fn clear_cache(&mut self) {
+        self.accounts.clear();
+        if let Some(lru) = &mut self.lru {
+            lru.clear();
+        }
+        self.contracts
+            .retain(|hash, _| *hash == KECCAK_EMPTY || *hash == B256::ZERO);
+        self.block_hashes.clear();
+    }
+
+    /// Resets the cache to the state of a newly created [CacheDB].
+    ///
+    /// Clears all accounts, contracts, logs and block hashes and inserts the default empty-code
+    /// entries into `contracts`, like [CacheDB::new] does. Auto clear and the account capacity
+    /// are kept.
+    pub fn clear(&mut self) {
+        self.clear_cache();
+        self.logs.clear();
+        self.contracts.insert(KECCAK_EMPTY, Bytecode::default());
+        self.contracts.insert(B256::ZERO, Bytecode::default());
+    }
+
+    /// Returns a snapshot of the cached state that can be restored with [CacheDB::restore].
+    pub fn snapshot(&self) -> CacheDbSnapshot {
+        CacheDbSnapshot {
+            accounts: self.accounts.clone(),
+            contracts: self.contracts.clone(),
+            logs: self.logs.clone(),
+            block_hashes: self.block_hashes.clone(),
+            lru: self.lru.clone(),
+        }
+    }
+
+    /// Restores the cached state from a snapshot taken with [CacheDB::snapshot].
+    ///
+    /// All changes made to the cache after the snapshot was taken are discarded.
+    pub fn restore(&mut self, snapshot: CacheDbSnapshot) {
+        self.accounts = snapshot.accounts;
+        self.contracts = snapshot.contracts;
+        self.logs = snapshot.logs;
+        self.block_hashes = snapshot.block_hashes;
+        self.lru = snapshot.lru;
+    }
+
     /// Inserts the account's code into the cache.
     ///
     /// Accounts objects and code are stored separately in the cache, this will take the code from the account and instead map it to the code hash.
@@ -79,8 +199,179 @@ impl<ExtDB> CacheDB<ExtDB> {
     /// Insert account info but not override storage
     pub fn insert_account_info(&mut self, address: Address, mut info: AccountInfo) {
         self.insert_contract(&mut info);
+        self.pin_account(address);
         self.accounts.entry(address).or_default().info = info;
     }
+
+    /// Returns an iterator over the cached accounts.
+    pub fn iter_accounts(&self) -> impl Iterator<Item = (&Address, &DbAccount)> {
+        self.accounts.iter()
+    }
+
+    /// Returns an iterator over the cached contracts, keyed by code hash.
+    pub fn iter_contracts(&self) -> impl Iterator<Item = (&B256, &Bytecode)> {
+        self.contracts.iter()
+    }
+
+    /// Returns the distribution of cached storage slots per account.
+    ///
+    /// Each entry maps a number of cached slots to the number of accounts that hold exactly
+    /// that many slots, sorted by the number of slots.
+    pub fn storage_slot_histogram(&self) -> Vec<(usize, usize)> {
+        let mut histogram = BTreeMap::new();
+        for account in self.accounts.values() {
+            *histogram.entry(account.storage.len()).or_insert(0) += 1;
+        }
+        histogram.into_iter().collect()
+    }
+
+    /// Returns the number of cached accounts in each [AccountState].
+    ///
+    /// The counts are ordered as [AccountState::None], [AccountState::Touched],
+    /// [AccountState::StorageCleared] and [AccountState::NotExisting].
+    pub fn account_state_counts(&self) -> [usize; 4] {
+        let mut counts = [0; 4];
+        for account in self.accounts.values() {
+            let index = match account.account_state {
+                AccountState::None => 0,
+                AccountState::Touched => 1,
+                AccountState::StorageCleared => 2,
+                AccountState::NotExisting => 3,
+            };
+            counts[index] += 1;
+        }
+        counts
+    }
+
+    /// Serializes the cached accounts, contracts, logs and block hashes into a snapshot.
+    ///
+    /// The snapshot is encoded as JSON, as `serde_json` is the only serialization format this
+    /// crate depends on. Map entries are sorted by key, so equal caches always produce the same
+    /// bytes. The underlying database is not part of the snapshot. Use
+    /// [CacheDB::from_snapshot_bytes] to load it back into an [InMemoryDB].
+    #[cfg(feature = "serde-json")]
+    pub fn to_snapshot_bytes(&self) -> Result<Vec<u8>, serde_json::Error> {
+        let snapshot = SnapshotRef {
+            accounts: self
+                .accounts
+                .iter()
+                .map(|(address, account)| {
+                    let account = DbAccountRef {
+                        info: &account.info,
+                        account_state: &account.account_state,
+                        storage: account.storage.iter().collect(),
+                    };
+                    (address, account)
+                })
+                .collect(),
+            contracts: self.contracts.iter().collect(),
+            logs: &self.logs,
+            block_hashes: self.block_hashes.iter().collect(),
+        };
+        serde_json::to_vec(&snapshot)
+    }
+
+    /// Makes room for a new account if the number of cached accounts is bounded.
+    ///
+    /// Does nothing if `address` is already cached.
+    fn reserve_account(&mut self, address: Address) {
+        let Some(lru) = &mut self.lru else {
+            return;
+        };
+        if self.accounts.contains_key(&address) {
+            return;
+        }
+        while self.accounts.len() >= lru.capacity {
+            let Some(evicted) = lru.pop_oldest() else {
+                break;
+            };
+            // Tracked accounts are never modified through the API, check the state anyway in
+            // case `accounts` was modified directly.
+            if self.accounts.get(&evicted).is_some_and(|account| {
+                matches!(
+                    account.account_state,
+                    AccountState::None | AccountState::NotExisting
+                )
+            }) {
+                self.accounts.remove(&evicted);
+            }
+        }
+    }
+
+    /// Inserts an account that was loaded from the underlying database, if it is not cached yet.
+    ///
+    /// Unlike [CacheDB::insert_account_info], the account is not modified, so it is tracked for
+    /// eviction if the number of cached accounts is bounded.
+    pub(super) fn insert_loaded_account(&mut self, address: Address, info: Option<AccountInfo>) {
+        if self.accounts.contains_key(&address) {
+            return;
+        }
+        self.reserve_account(address);
+        self.accounts.insert(address, info.into());
+        if let Some(lru) = &mut self.lru {
+            lru.insert(address);
+        }
+    }
+
+    /// Excludes the account from eviction, as it may be modified.
+    fn pin_account(&mut self, address: Address) {
+        if let Some(lru) = &mut self.lru {
+            lru.remove(&address);
+        }
+    }
+
+    /// Freezes the cache into an immutable [FrozenCacheDB].
+    ///
+    /// The frozen cache only implements [DatabaseRef] and can be shared between threads for
+    /// concurrent reads. Use [FrozenCacheDB::thaw] to get the writable [CacheDB] back.
+    pub fn freeze(self) -> FrozenCacheDB<ExtDB> {
+        FrozenCacheDB(self)
+    }
+}
+
+#[cfg(feature = "serde-json")]
+impl CacheDB<EmptyDB> {
+    /// Loads an [InMemoryDB] from a JSON snapshot created by [CacheDB::to_snapshot_bytes].
+    pub fn from_snapshot_bytes(bytes: &[u8]) -> Result<Self, serde_json::Error> {
+        let snapshot: Snapshot = serde_json::from_slice(bytes)?;
+        let mut db = Self::new_empty(EmptyDB::default());
+        db.accounts = snapshot.accounts;
+        db.contracts = snapshot.contracts;
+        db.logs = snapshot.logs;
+        db.block_hashes = snapshot.block_hashes;
+        Ok(db)
+    }
+}
+
+/// Serialized form of a [CacheDB] without the underlying database.
+///
+/// Maps are collected into [BTreeMap]s so that entries are serialized in key order.
+#[cfg(feature = "serde-json")]
+#[derive(serde::Serialize)]
+struct SnapshotRef<'a> {
+    accounts: BTreeMap<&'a Address, DbAccountRef<'a>>,
+    contracts: BTreeMap<&'a B256, &'a Bytecode>,
+    logs: &'a Vec<Log>,
+    block_hashes: BTreeMap<&'a U256, &'a B256>,
+}
+
+/// Serialized form of a [DbAccount] with storage slots in key order.
+#[cfg(feature = "serde-json")]
+#[derive(serde::Serialize)]
+struct DbAccountRef<'a> {
+    info: &'a AccountInfo,
+    account_state: &'a AccountState,
+    storage: BTreeMap<&'a U256, &'a U256>,
+}
+
+/// Owned counterpart of [SnapshotRef].
+#[cfg(feature = "serde-json")]
+#[derive(serde::Deserialize)]
+struct Snapshot {
+    accounts: HashMap<Address, DbAccount>,
+    contracts: HashMap<B256, Bytecode>,
+    logs: Vec<Log>,
+    block_hashes: HashMap<U256, B256>,
 }
 
 impl<ExtDB: DatabaseRef> CacheDB<ExtDB> {
@@ -88,6 +379,9 @@ impl<ExtDB: DatabaseRef> CacheDB<ExtDB> {
     ///
     /// If the account was not found in the cache, it will be loaded from the underlying database.
     pub fn load_account(&mut self, address: Address) -> Result<&mut DbAccount, ExtDB::Error> {
+        self.reserve_account(address);
+        // the account is returned mutably, so it can't be restored from the database anymore.
+        self.pin_account(address);
         let db = &self.db;
         match self.accounts.entry(address) {
             Entry::Occupied(entry) => Ok(entry.into_mut()),
@@ -125,6 +419,46 @@ impl<ExtDB: DatabaseRef> CacheDB<ExtDB> {
         account.storage = storage.into_iter().collect();
         Ok(())
     }
+
+    /// Loads the given accounts into the cache.
+    pub fn warm_accounts(&mut self, addresses: &[Address]) -> Result<(), ExtDB::Error> {
+        for address in addresses {
+            self.basic(*address)?;
+        }
+        Ok(())
+    }
+
+    /// Loads the given storage slots, and the accounts they belong to, into the cache.
+    pub fn warm_storage(&mut self, entries: &[(Address, U256)]) -> Result<(), ExtDB::Error> {
+        for (address, index) in entries {
+            self.storage(*address, *index)?;
+        }
+        Ok(())
+    }
+
+    /// Replays a sequence of database accesses to bring the cache into a known warm state.
+    ///
+    /// Each event is issued through the [Database] implementation, loading the accessed data
+    /// from the underlying database if it is not cached yet.
+    pub fn replay_access_log(&mut self, log: &[AccessEvent]) -> Result<(), ExtDB::Error> {
+        for event in log {
+            match *event {
+                AccessEvent::Basic(address) => {
+                    self.basic(address)?;
+                }
+                AccessEvent::CodeByHash(code_hash) => {
+                    self.code_by_hash(code_hash)?;
+                }
+                AccessEvent::Storage(address, index) => {
+                    self.storage(address, index)?;
+                }
+                AccessEvent::BlockHash(number) => {
+                    self.block_hash(number)?;
+                }
+            }
+        }
+        Ok(())
+    }
 }
 
 impl<ExtDB> DatabaseCommit for CacheDB<ExtDB> {
@@ -133,6 +467,7 @@ impl<ExtDB> DatabaseCommit for CacheDB<ExtDB> {
             if !account.is_touched() {
                 continue;
             }
+            self.pin_account(address);
             if account.is_selfdestructed() {
                 let db_account = self.accounts.entry(address).or_default();
                 db_account.storage.clear();
@@ -169,19 +504,15 @@ impl<ExtDB: DatabaseRef> Database for CacheDB<ExtDB> {
     type Error = ExtDB::Error;
 
     fn basic(&mut self, address: Address) -> Result<Option<AccountInfo>, Self::Error> {
-        let basic = match self.accounts.entry(address) {
-            Entry::Occupied(entry) => entry.into_mut(),
-            Entry::Vacant(entry) => entry.insert(
-                self.db
-                    .basic_ref(address)?
-                    .map(|info| DbAccount {
-                        info,
-                        ..Default::default()
-                    })
-                    .unwrap_or_else(DbAccount::new_not_existing),
-            ),
-        };
-        Ok(basic.info())
+        if let Some(account) = self.accounts.get(&address) {
+            if let Some(lru) = &mut self.lru {
+                lru.touch(&address);
+            }
+            return Ok(account.info());
+        }
+        let info = self.db.basic_ref(address)?;
+        self.insert_loaded_account(address, info.clone());
+        Ok(info)
     }
 
     fn code_by_hash(&mut self, code_hash: B256) -> Result<Bytecode, Self::Error> {
@@ -198,8 +529,12 @@ impl<ExtDB: DatabaseRef> Database for CacheDB<ExtDB> {
     ///
     /// It is assumed that account is already loaded.
     fn storage(&mut self, address: Address, index: U256) -> Result<U256, Self::Error> {
+        self.reserve_account(address);
         match self.accounts.entry(address) {
             Entry::Occupied(mut acc_entry) => {
+                if let Some(lru) = &mut self.lru {
+                    lru.touch(&address);
+                }
                 let acc_entry = acc_entry.get_mut();
                 match acc_entry.storage.entry(index) {
                     Entry::Occupied(entry) => Ok(*entry.get()),
@@ -229,6 +564,9 @@ impl<ExtDB: DatabaseRef> Database for CacheDB<ExtDB> {
                     (info.into(), U256::ZERO)
                 };
                 acc_entry.insert(account);
+                if let Some(lru) = &mut self.lru {
+                    lru.insert(address);
+                }
                 Ok(value)
             }
         }
@@ -290,6 +628,137 @@ impl<ExtDB: DatabaseRef> DatabaseRef for CacheDB<ExtDB> {
     }
 }
 
+/// Cached state of a [CacheDB], see [CacheDB::snapshot].
+#[derive(Debug, Clone)]
+pub struct CacheDbSnapshot {
+    accounts: HashMap<Address, DbAccount>,
+    contracts: HashMap<B256, Bytecode>,
+    logs: Vec<Log>,
+    block_hashes: HashMap<U256, B256>,
+    lru: Option<AccountLru>,
+}
+
+/// Recency order of the cached accounts that can be evicted.
+///
+/// Only accounts that were loaded from the underlying database and not modified since are
+/// tracked, as they can be loaded again after eviction.
+#[derive(Debug, Clone)]
+struct AccountLru {
+    /// Maximum number of cached accounts.
+    capacity: usize,
+    /// Last used tick, incremented on every access.
+    tick: u64,
+    /// Tick of the last access of each tracked account.
+    ticks: HashMap<Address, u64>,
+    /// Tracked accounts ordered by their last access.
+    order: BTreeMap<u64, Address>,
+}
+
+impl AccountLru {
+    fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            tick: 0,
+            ticks: HashMap::new(),
+            order: BTreeMap::new(),
+        }
+    }
+
+    /// Starts tracking the account as the most recently used one.
+    fn insert(&mut self, address: Address) {
+        self.tick += 1;
+        if let Some(old) = self.ticks.insert(address, self.tick) {
+            self.order.remove(&old);
+        }
+        self.order.insert(self.tick, address);
+    }
+
+    /// Marks the account as the most recently used one, if it is tracked.
+    fn touch(&mut self, address: &Address) {
+        if let Some(tick) = self.ticks.get_mut(address) {
+            self.order.remove(tick);
+            self.tick += 1;
+            *tick = self.tick;
+            self.order.insert(self.tick, *address);
+        }
+    }
+
+    /// Stops tracking the account.
+    fn remove(&mut self, address: &Address) {
+        if let Some(tick) = self.ticks.remove(address) {
+            self.order.remove(&tick);
+        }
+    }
+
+    /// Removes and returns the least recently used account.
+    fn pop_oldest(&mut self) -> Option<Address> {
+        let (_, address) = self.order.pop_first()?;
+        self.ticks.remove(&address);
+        Some(address)
+    }
+
+    fn clear(&mut self) {
+        self.ticks.clear();
+        self.order.clear();
+    }
+}
+
+/// A single [Database] access, see [CacheDB::replay_access_log].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum AccessEvent {
+    /// [Database::basic] call for the given address.
+    Basic(Address),
+    /// [Database::code_by_hash] call for the given code hash.
+    CodeByHash(B256),
+    /// [Database::storage] call for the given address and slot index.
+    Storage(Address, U256),
+    /// [Database::block_hash] call for the given block number.
+    BlockHash(U256),
+}
+
+/// An immutable view of a [CacheDB].
+///
+/// Created by [CacheDB::freeze]. Reads never populate the cache, they fall through to the
+/// underlying database on a miss instead, so the frozen cache is [Sync] whenever `ExtDB` is and
+/// can be shared between threads for parallel reads.
+///
+/// Note: writes require thawing back into a [CacheDB] with [FrozenCacheDB::thaw].
+#[derive(Debug, Clone)]
+pub struct FrozenCacheDB<ExtDB>(CacheDB<ExtDB>);
+
+impl<ExtDB> FrozenCacheDB<ExtDB> {
+    /// Returns a reference to the frozen [CacheDB].
+    pub fn inner(&self) -> &CacheDB<ExtDB> {
+        &self.0
+    }
+
+    /// Thaws the frozen cache back into a writable [CacheDB].
+    pub fn thaw(self) -> CacheDB<ExtDB> {
+        self.0
+    }
+}
+
+impl<ExtDB: DatabaseRef> DatabaseRef for FrozenCacheDB<ExtDB> {
+    type Error = ExtDB::Error;
+
+    fn basic_ref(&self, address: Address) -> Result<Option<AccountInfo>, Self::Error> {
+        self.0.basic_ref(address)
+    }
+
+    fn code_by_hash_ref(&self, code_hash: B256) -> Result<Bytecode, Self::Error> {
+        self.0.code_by_hash_ref(code_hash)
+    }
+
+    fn storage_ref(&self, address: Address, index: U256) -> Result<U256, Self::Error> {
+        self.0.storage_ref(address, index)
+    }
+
+    fn block_hash_ref(&self, number: U256) -> Result<B256, Self::Error> {
+        self.0.block_hash_ref(number)
+    }
+}
+
 #[derive(Debug, Clone, Default)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct DbAccount {
@@ -315,6 +784,65 @@ impl DbAccount {
             Some(self.info.clone())
         }
     }
+
+    /// Returns the number of heap bytes used by this account.
+    ///
+    /// This is the length of the bytecode held in `info.code` (if any) and, for analyzed legacy
+    /// bytecode, of its jump table. The decoded sections of EOF bytecode and reference counts of
+    /// shared buffers are not included.
+    ///
+    /// The allocation of the `storage` map is derived from the map capacity, following the
+    /// hashbrown table layout: one `(U256, U256)` entry and one control byte per bucket, plus a
+    /// trailing group of control bytes.
+    pub fn mem_usage(&self) -> usize {
+        /// Width of a hashbrown control group, following hashbrown's group selection: 16 bytes
+        /// with SSE2 on x86 or LSX on loongarch64, 8 bytes with NEON on aarch64. Otherwise the
+        /// generic group is used, which is a `u64` on 64-bit targets and wasm32 and a `u32`
+        /// elsewhere.
+        const GROUP_WIDTH: usize = if cfg!(miri) {
+            if cfg!(any(target_pointer_width = "64", target_arch = "wasm32")) {
+                8
+            } else {
+                4
+            }
+        } else if cfg!(any(
+            all(
+                any(target_arch = "x86", target_arch = "x86_64"),
+                target_feature = "sse2"
+            ),
+            all(target_arch = "loongarch64", target_feature = "lsx")
+        )) {
+            16
+        } else if cfg!(any(target_pointer_width = "64", target_arch = "wasm32")) {
+            // NEON groups on aarch64 are 8 bytes wide as well.
+            8
+        } else {
+            4
+        };
+
+        let code = self.info.code.as_ref().map_or(0, |code| match code {
+            Bytecode::LegacyAnalyzed(analyzed) => {
+                analyzed.bytecode().len() + analyzed.jump_table().as_slice().len()
+            }
+            _ => code.bytes_slice().len(),
+        });
+
+        let capacity = self.storage.capacity();
+        let storage = if capacity == 0 {
+            0
+        } else {
+            // Inverse of hashbrown's `bucket_mask_to_capacity`, tables with fewer than 8 buckets
+            // use all but one bucket, larger ones have a load factor of 7/8.
+            let buckets = if capacity < 8 {
+                capacity + 1
+            } else {
+                capacity / 7 * 8
+            };
+            buckets * core::mem::size_of::<(U256, U256)>() + buckets + GROUP_WIDTH
+        };
+
+        code + storage
+    }
 }
 
 impl From<Option<AccountInfo>> for DbAccount {
@@ -410,8 +938,11 @@ impl Database for BenchmarkDB {
 
 #[cfg(test)]
 mod tests {
-    use super::{CacheDB, EmptyDB};
-    use crate::primitives::{db::Database, AccountInfo, Address, U256};
+    use super::{AccessEvent, AccountState, CacheDB, EmptyDB};
+    use crate::primitives::{
+        db::{Database, DatabaseRef},
+        AccountInfo, Address, U256,
+    };
 
     #[test]
     fn test_insert_account_storage() {
@@ -465,6 +996,329 @@ mod tests {
         assert_eq!(new_state.storage(account, key1), Ok(value1));
     }
 
+    #[test]
+    fn test_iter_accounts() {
+        let mut state = CacheDB::new(EmptyDB::default());
+        for i in 1..=3 {
+            state.insert_account_info(
+                Address::with_last_byte(i),
+                AccountInfo {
+                    nonce: i as u64,
+                    ..Default::default()
+                },
+            );
+        }
+
+        let mut accounts: Vec<_> = state
+            .iter_accounts()
+            .map(|(address, account)| (*address, account.info.nonce))
+            .collect();
+        accounts.sort();
+        assert_eq!(
+            accounts,
+            (1..=3)
+                .map(|i| (Address::with_last_byte(i), i as u64))
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(state.iter_contracts().count(), 2);
+    }
+
+    #[test]
+    fn test_storage_slot_histogram() {
+        let mut state = CacheDB::new(EmptyDB::default());
+        for i in 0..3u8 {
+            state.insert_account_info(Address::with_last_byte(i), AccountInfo::default());
+        }
+        let account = Address::with_last_byte(42);
+        for slot in 0..5 {
+            state
+                .insert_account_storage(account, U256::from(slot), U256::from(1))
+                .unwrap();
+        }
+
+        assert_eq!(state.storage_slot_histogram(), vec![(0, 3), (5, 1)]);
+    }
+
+    #[test]
+    fn test_new_empty() {
+        let state = CacheDB::new_empty(EmptyDB::default());
+        assert!(state.contracts.is_empty());
+        assert!(state.accounts.is_empty());
+
+        let state = CacheDB::new(EmptyDB::default());
+        assert_eq!(state.contracts.len(), 2);
+    }
+
+    #[test]
+    fn test_account_state_counts() {
+        let mut state = CacheDB::new(EmptyDB::default());
+        state.insert_account_info(Address::with_last_byte(1), AccountInfo::default());
+        state.insert_account_info(Address::with_last_byte(2), AccountInfo::default());
+        state
+            .replace_account_storage(Address::with_last_byte(3), Default::default())
+            .unwrap();
+        // not existing in the underlying database
+        state.basic(Address::with_last_byte(4)).unwrap();
+        state
+            .accounts
+            .entry(Address::with_last_byte(5))
+            .or_default()
+            .account_state = AccountState::Touched;
+
+        assert_eq!(state.account_state_counts(), [2, 1, 1, 1]);
+    }
+
+    #[test]
+    fn test_auto_clear() {
+        let account = Address::with_last_byte(42);
+        let mut init_state = CacheDB::new(EmptyDB::default());
+        init_state.insert_account_info(
+            account,
+            AccountInfo {
+                nonce: 42,
+                ..Default::default()
+            },
+        );
+
+        let mut state = CacheDB::new(init_state);
+        state.basic(account).unwrap();
+        state.begin_transaction();
+        assert_eq!(state.accounts.len(), 1);
+
+        state.set_auto_clear(true);
+        state.begin_transaction();
+        assert!(state.accounts.is_empty());
+        assert_eq!(state.contracts.len(), 2);
+        assert_eq!(state.basic(account).unwrap().unwrap().nonce, 42);
+    }
+
+    #[test]
+    fn test_replay_access_log() {
+        let account = Address::with_last_byte(42);
+        let (key, value) = (U256::from(123), U256::from(456));
+        let mut init_state = CacheDB::new(EmptyDB::default());
+        init_state.insert_account_info(account, AccountInfo::default());
+        init_state
+            .insert_account_storage(account, key, value)
+            .unwrap();
+
+        let log = [
+            AccessEvent::Basic(account),
+            AccessEvent::Storage(account, key),
+            AccessEvent::Basic(Address::with_last_byte(1)),
+            AccessEvent::BlockHash(U256::from(7)),
+        ];
+        let mut state = CacheDB::new(init_state);
+        state.replay_access_log(&log).unwrap();
+
+        assert_eq!(state.accounts.len(), 2);
+        assert_eq!(state.accounts[&account].storage.get(&key), Some(&value));
+        assert!(state.block_hashes.contains_key(&U256::from(7)));
+    }
+
+    #[test]
+    fn test_db_account_mem_usage() {
+        use super::DbAccount;
+        use crate::{
+            interpreter::analysis::to_analysed,
+            primitives::{Bytecode, Bytes},
+        };
+
+        let mut account = DbAccount::from(AccountInfo {
+            code: None,
+            ..Default::default()
+        });
+        assert_eq!(account.mem_usage(), 0);
+
+        account.info.code = Some(Bytecode::new_raw(Bytes::from(vec![0u8; 100])));
+        assert_eq!(account.mem_usage(), 100);
+
+        // analysis pads the code with 33 bytes and adds a jump table of one bit per byte.
+        account.info.code = Some(to_analysed(Bytecode::new_raw(Bytes::from(vec![0u8; 100]))));
+        assert_eq!(account.mem_usage(), 133 + 17);
+
+        for slot in 0..10 {
+            account.storage.insert(U256::from(slot), U256::from(slot));
+        }
+        // 10 slots need a table of 16 buckets with 14 usable ones.
+        assert_eq!(account.storage.capacity(), 14);
+        let storage = 16 * 64 + 16;
+        if cfg!(all(target_arch = "x86_64", not(miri))) {
+            assert_eq!(account.mem_usage(), 133 + 17 + storage + 16);
+        } else if cfg!(any(target_arch = "aarch64", target_arch = "wasm32")) {
+            assert_eq!(account.mem_usage(), 133 + 17 + storage + 8);
+        }
+    }
+
+    #[test]
+    fn test_account_capacity_eviction_order() {
+        let mut init_state = CacheDB::new(EmptyDB::default());
+        for i in 1..=3 {
+            init_state.insert_account_info(
+                Address::with_last_byte(i),
+                AccountInfo {
+                    nonce: i as u64,
+                    ..Default::default()
+                },
+            );
+        }
+
+        let mut state = CacheDB::with_account_capacity(init_state, 2);
+        assert_eq!(state.account_capacity(), Some(2));
+        state.basic(Address::with_last_byte(1)).unwrap();
+        state
+            .storage(Address::with_last_byte(2), U256::from(1))
+            .unwrap();
+        // account 1 is now more recently used than account 2
+        state.basic(Address::with_last_byte(1)).unwrap();
+        state.basic(Address::with_last_byte(3)).unwrap();
+
+        assert_eq!(state.accounts.len(), 2);
+        assert!(state.accounts.contains_key(&Address::with_last_byte(1)));
+        assert!(!state.accounts.contains_key(&Address::with_last_byte(2)));
+        assert!(state.accounts.contains_key(&Address::with_last_byte(3)));
+    }
+
+    #[test]
+    fn test_account_capacity_reload() {
+        use crate::{
+            db::DatabaseCommit,
+            primitives::{Account, HashMap},
+        };
+
+        let (key, value) = (U256::from(123), U256::from(456));
+        let mut init_state = CacheDB::new(EmptyDB::default());
+        for i in 1..=3 {
+            let address = Address::with_last_byte(i);
+            init_state.insert_account_info(address, AccountInfo::default());
+            init_state
+                .insert_account_storage(address, key, value)
+                .unwrap();
+        }
+
+        let mut state = CacheDB::with_account_capacity(init_state, 1);
+        let committed = Address::with_last_byte(1);
+        let mut account = Account::from(state.basic(committed).unwrap().unwrap());
+        account.info.nonce = 42;
+        account.mark_touch();
+        state.commit(HashMap::from([(committed, account)]));
+
+        state.basic(Address::with_last_byte(2)).unwrap();
+        state.basic(Address::with_last_byte(3)).unwrap();
+        // the committed account is kept even though the cache is over capacity.
+        assert_eq!(state.accounts.len(), 2);
+        assert_eq!(state.basic(committed).unwrap().unwrap().nonce, 42);
+
+        // evicted account is reloaded with its storage.
+        assert!(!state.accounts.contains_key(&Address::with_last_byte(2)));
+        assert_eq!(state.storage(Address::with_last_byte(2), key), Ok(value));
+        assert!(!state.accounts.contains_key(&Address::with_last_byte(3)));
+    }
+
+    #[test]
+    fn test_clear() {
+        use crate::primitives::{Bytecode, Bytes, Log};
+
+        let account = Address::with_last_byte(42);
+        let mut init_state = CacheDB::new(EmptyDB::default());
+        init_state.insert_account_info(
+            account,
+            AccountInfo {
+                nonce: 42,
+                ..Default::default()
+            },
+        );
+
+        let mut state = CacheDB::new_empty(init_state);
+        state.basic(account).unwrap();
+        state.insert_account_info(
+            Address::with_last_byte(1),
+            AccountInfo {
+                code: Some(Bytecode::new_raw(Bytes::from_static(&[0x60, 0x00]))),
+                ..Default::default()
+            },
+        );
+        state.logs.push(Log::default());
+        state.block_hash(U256::from(1)).unwrap();
+
+        state.clear();
+        assert!(state.accounts.is_empty());
+        assert!(state.logs.is_empty());
+        assert!(state.block_hashes.is_empty());
+        assert_eq!(state.contracts.len(), 2);
+
+        // previously cached account is loaded again from the underlying database.
+        assert!(!state.accounts.contains_key(&account));
+        assert_eq!(state.basic(account).unwrap().unwrap().nonce, 42);
+    }
+
+    #[test]
+    fn test_snapshot_restore() {
+        let account = Address::with_last_byte(42);
+        let (key, value) = (U256::from(123), U256::from(456));
+        let mut state = CacheDB::new(EmptyDB::default());
+        state.insert_account_storage(account, key, value).unwrap();
+
+        let snapshot = state.snapshot();
+        state
+            .insert_account_storage(account, key, U256::from(789))
+            .unwrap();
+        state
+            .insert_account_storage(Address::with_last_byte(1), key, value)
+            .unwrap();
+        assert_eq!(state.storage(account, key), Ok(U256::from(789)));
+
+        state.restore(snapshot);
+        assert_eq!(state.storage(account, key), Ok(value));
+        assert_eq!(state.accounts.len(), 1);
+    }
+
+    #[test]
+    fn test_warm_accounts_and_storage() {
+        let (key, value) = (U256::from(123), U256::from(456));
+        let mut init_state = CacheDB::new(EmptyDB::default());
+        for i in 1..=2 {
+            let address = Address::with_last_byte(i);
+            init_state.insert_account_info(address, AccountInfo::default());
+            init_state
+                .insert_account_storage(address, key, value)
+                .unwrap();
+        }
+
+        let mut state = CacheDB::new(init_state);
+        state.warm_accounts(&[Address::with_last_byte(1)]).unwrap();
+        state
+            .warm_storage(&[(Address::with_last_byte(2), key)])
+            .unwrap();
+
+        assert!(state.accounts.contains_key(&Address::with_last_byte(1)));
+        assert_eq!(
+            state.accounts[&Address::with_last_byte(2)]
+                .storage
+                .get(&key),
+            Some(&value)
+        );
+    }
+
+    #[test]
+    fn test_freeze_thaw() {
+        fn assert_sync<T: Sync>(_: &T) {}
+
+        let account = Address::with_last_byte(42);
+        let (key, value) = (U256::from(123), U256::from(456));
+        let mut state = CacheDB::new(EmptyDB::default());
+        state.insert_account_storage(account, key, value).unwrap();
+
+        let frozen = state.freeze();
+        assert_sync(&frozen);
+        assert_eq!(frozen.storage_ref(account, key), Ok(value));
+        assert_eq!(frozen.basic_ref(Address::with_last_byte(1)), Ok(None));
+        assert_eq!(frozen.inner().accounts.len(), 1);
+
+        let mut state = frozen.thaw();
+        assert_eq!(state.storage(account, key), Ok(value));
+    }
+
     #[cfg(feature = "serde-json")]
     #[test]
     fn test_serialize_deserialize_cachedb() {
@@ -488,4 +1342,67 @@ mod tests {
             nonce
         );
     }
+
+    #[cfg(feature = "serde-json")]
+    #[test]
+    fn test_snapshot_bytes_roundtrip() {
+        use crate::primitives::{Bytecode, Bytes, B256};
+
+        let account = Address::with_last_byte(69);
+        let (key, value) = (U256::from(123), U256::from(456));
+        let mut init_state = CacheDB::new(EmptyDB::default());
+        init_state.insert_account_info(
+            account,
+            AccountInfo {
+                nonce: 420,
+                code: Some(Bytecode::new_raw(Bytes::from_static(&[0x60, 0x00]))),
+                ..Default::default()
+            },
+        );
+        init_state
+            .insert_account_storage(account, key, value)
+            .unwrap();
+        init_state
+            .block_hashes
+            .insert(U256::from(1), B256::with_last_byte(1));
+
+        let bytes = init_state.to_snapshot_bytes().unwrap();
+        let mut state = CacheDB::from_snapshot_bytes(&bytes).unwrap();
+        assert_eq!(state.contracts, init_state.contracts);
+        assert_eq!(state.block_hashes, init_state.block_hashes);
+        assert_eq!(state.accounts.len(), 1);
+        assert_eq!(state.basic(account).unwrap().unwrap().nonce, 420);
+        assert_eq!(state.storage(account, key), Ok(value));
+    }
+
+    #[cfg(feature = "serde-json")]
+    #[test]
+    fn test_snapshot_bytes_deterministic() {
+        use crate::primitives::B256;
+
+        // builds the same state, inserting entries in the given order.
+        let build = |order: &[u8]| {
+            let mut state = CacheDB::new(EmptyDB::default());
+            for &i in order {
+                let account = Address::with_last_byte(i);
+                state.insert_account_info(account, AccountInfo::default());
+                for &slot in order {
+                    state
+                        .insert_account_storage(account, U256::from(slot), U256::from(i))
+                        .unwrap();
+                }
+                state
+                    .block_hashes
+                    .insert(U256::from(i), B256::with_last_byte(i));
+            }
+            state
+        };
+        let order: Vec<u8> = (0..32).collect();
+        let reversed: Vec<u8> = order.iter().rev().copied().collect();
+
+        assert_eq!(
+            build(&order).to_snapshot_bytes().unwrap(),
+            build(&reversed).to_snapshot_bytes().unwrap()
+        );
+    }
 }
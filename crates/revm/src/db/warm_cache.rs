@@ -0,0 +1,165 @@
+use super::{CacheDB, DatabaseRef};
+use crate::primitives::{AccountInfo, Address, Bytecode, HashMap, B256, U256};
+use std::sync::{PoisonError, RwLock};
+
+/// A [CacheDB] wrapper that can be warmed through a shared reference.
+///
+/// [DatabaseRef] reads on a [CacheDB] can't populate it, so accounts that miss the cache are
+/// loaded from the underlying database on every access. This wrapper keeps a side cache behind a
+/// [RwLock] that is populated during `basic_ref` reads, which allows warming from multiple
+/// threads when `&mut` access isn't available.
+///
+/// Warmed accounts are moved into the [CacheDB] with [WarmCache::into_inner].
+#[derive(Debug)]
+pub struct WarmCache<ExtDB> {
+    cache: CacheDB<ExtDB>,
+    warmed: RwLock<HashMap<Address, Option<AccountInfo>>>,
+}
+
+impl<ExtDB> WarmCache<ExtDB> {
+    /// Wraps the given [CacheDB].
+    pub fn new(cache: CacheDB<ExtDB>) -> Self {
+        Self {
+            cache,
+            warmed: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Returns a reference to the wrapped [CacheDB].
+    pub fn cache(&self) -> &CacheDB<ExtDB> {
+        &self.cache
+    }
+
+    /// Returns the number of accounts in the side cache.
+    pub fn warmed_len(&self) -> usize {
+        self.warmed
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .len()
+    }
+
+    /// Consumes the wrapper and returns the [CacheDB] with all warmed accounts inserted.
+    ///
+    /// Accounts that are already present in the [CacheDB] are not overridden. Warmed accounts
+    /// are plain database reads, so a cache created with [CacheDB::with_account_capacity] can
+    /// evict them to stay within its capacity.
+    pub fn into_inner(self) -> CacheDB<ExtDB> {
+        let mut cache = self.cache;
+        let warmed = self
+            .warmed
+            .into_inner()
+            .unwrap_or_else(PoisonError::into_inner);
+        for (address, info) in warmed {
+            cache.insert_loaded_account(address, info);
+        }
+        cache
+    }
+}
+
+impl<ExtDB: DatabaseRef> WarmCache<ExtDB> {
+    /// Loads the given accounts into the side cache.
+    pub fn prefetch_ref(&self, addresses: &[Address]) -> Result<(), ExtDB::Error> {
+        for address in addresses {
+            self.basic_ref(*address)?;
+        }
+        Ok(())
+    }
+}
+
+impl<ExtDB: DatabaseRef> DatabaseRef for WarmCache<ExtDB> {
+    type Error = ExtDB::Error;
+
+    fn basic_ref(&self, address: Address) -> Result<Option<AccountInfo>, Self::Error> {
+        if let Some(account) = self.cache.accounts.get(&address) {
+            return Ok(account.info());
+        }
+        if let Some(info) = self
+            .warmed
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(&address)
+        {
+            return Ok(info.clone());
+        }
+        let info = self.cache.db.basic_ref(address)?;
+        self.warmed
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert(address, info.clone());
+        Ok(info)
+    }
+
+    fn code_by_hash_ref(&self, code_hash: B256) -> Result<Bytecode, Self::Error> {
+        self.cache.code_by_hash_ref(code_hash)
+    }
+
+    fn storage_ref(&self, address: Address, index: U256) -> Result<U256, Self::Error> {
+        self.cache.storage_ref(address, index)
+    }
+
+    fn block_hash_ref(&self, number: U256) -> Result<B256, Self::Error> {
+        self.cache.block_hash_ref(number)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::WarmCache;
+    use crate::{
+        db::{CacheDB, EmptyDB},
+        primitives::{db::Database, AccountInfo, Address},
+    };
+
+    #[test]
+    fn test_prefetch_ref() {
+        let account = Address::with_last_byte(42);
+        let mut init_state = CacheDB::new(EmptyDB::default());
+        init_state.insert_account_info(
+            account,
+            AccountInfo {
+                nonce: 42,
+                ..Default::default()
+            },
+        );
+
+        let warm = WarmCache::new(CacheDB::new(init_state));
+        std::thread::scope(|s| {
+            s.spawn(|| warm.prefetch_ref(&[account]).unwrap());
+            s.spawn(|| warm.prefetch_ref(&[Address::with_last_byte(1)]).unwrap());
+        });
+        assert_eq!(warm.warmed_len(), 2);
+        assert!(warm.cache().accounts.is_empty());
+
+        let state = warm.into_inner();
+        assert_eq!(state.accounts.get(&account).unwrap().info.nonce, 42);
+        assert!(state
+            .accounts
+            .get(&Address::with_last_byte(1))
+            .unwrap()
+            .info()
+            .is_none());
+    }
+
+    #[test]
+    fn test_into_inner_bounded() {
+        let mut init_state = CacheDB::new(EmptyDB::default());
+        for i in 1..=3 {
+            init_state.insert_account_info(Address::with_last_byte(i), AccountInfo::default());
+        }
+
+        let warm = WarmCache::new(CacheDB::with_account_capacity(init_state, 2));
+        warm.prefetch_ref(&(1..=4).map(Address::with_last_byte).collect::<Vec<_>>())
+            .unwrap();
+        assert_eq!(warm.warmed_len(), 4);
+
+        let mut state = warm.into_inner();
+        assert_eq!(state.accounts.len(), 2);
+
+        // warmed accounts stay evictable.
+        state.basic(Address::with_last_byte(5)).unwrap();
+        state.basic(Address::with_last_byte(6)).unwrap();
+        assert_eq!(state.accounts.len(), 2);
+        assert!(state.accounts.contains_key(&Address::with_last_byte(5)));
+        assert!(state.accounts.contains_key(&Address::with_last_byte(6)));
+    }
+}